@@ -0,0 +1,65 @@
+//! Error handling types for this crate.
+
+use std::fmt;
+use std::io;
+
+/// Error type for all functions in this crate.
+#[derive(Debug)]
+pub enum NiftiError {
+    /// An error from the underlying I/O operations.
+    Io(io::Error),
+    /// The number of bytes read did not match what was expected.
+    IncompatibleLength(usize, usize),
+    /// Failed to reserve enough capacity to read an extension's data.
+    ReserveExtended(usize, std::collections::TryReserveError),
+    /// An extension declared an invalid `esize`: it must be at least 8
+    /// and a multiple of 16.
+    InvalidExtensionSize(i32),
+    /// An extension's payload could not be interpreted by the requested
+    /// codec, for the given `ecode`.
+    InvalidExtensionPayload(i32),
+}
+
+impl fmt::Display for NiftiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NiftiError::Io(e) => write!(f, "I/O error: {}", e),
+            NiftiError::IncompatibleLength(got, expected) => write!(
+                f,
+                "incompatible length: got {} bytes, expected {}",
+                got, expected
+            ),
+            NiftiError::ReserveExtended(size, e) => write!(
+                f,
+                "failed to reserve {} bytes for extension data: {}",
+                size, e
+            ),
+            NiftiError::InvalidExtensionSize(esize) => {
+                write!(f, "invalid extension size: esize is {}", esize)
+            }
+            NiftiError::InvalidExtensionPayload(ecode) => write!(
+                f,
+                "could not interpret extension payload for ecode {}",
+                ecode
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NiftiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NiftiError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NiftiError {
+    fn from(e: io::Error) -> Self {
+        NiftiError::Io(e)
+    }
+}
+
+/// Result type alias for this crate.
+pub type Result<T> = std::result::Result<T, NiftiError>;
@@ -0,0 +1,434 @@
+//! Typed interpretations of an [`Extension`]'s raw payload, keyed on its
+//! `ecode`. Implement [`ExtensionCodec`] to add support for interpreting
+//! (and producing) further extension payloads beyond the raw bytes.
+
+use super::{Extension, NiftiEcode};
+use crate::error::{NiftiError, Result};
+
+/// A typed interpretation of an [`Extension`]'s raw data.
+///
+/// Implementations are free to pick whichever `ecode` they represent; the
+/// `encode` direction is expected to tag the resulting [`Extension`]
+/// accordingly.
+pub trait ExtensionCodec: Sized {
+    /// Decode the structured representation out of the extension's raw data.
+    fn decode(extension: &Extension) -> Result<Self>;
+
+    /// Encode this value back into a raw extension.
+    fn encode(&self) -> Extension;
+}
+
+/// A plain-text comment extension (`NiftiEcodeComment`).
+///
+/// The payload is decoded as UTF-8, falling back to a Latin-1
+/// interpretation (one byte per code point) for files that used a
+/// narrower encoding. Trailing NUL padding bytes (used to round `esize`
+/// up to a multiple of 16) are trimmed off.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CommentExtension(pub String);
+
+impl ExtensionCodec for CommentExtension {
+    fn decode(extension: &Extension) -> Result<Self> {
+        let trimmed = trim_trailing_nul(extension.data());
+        let text = String::from_utf8(trimmed.to_vec())
+            .unwrap_or_else(|_| trimmed.iter().map(|&b| b as char).collect());
+        Ok(CommentExtension(text))
+    }
+
+    fn encode(&self) -> Extension {
+        Extension::from_str(NiftiEcode::NiftiEcodeComment as i32, &self.0)
+    }
+}
+
+fn trim_trailing_nul(data: &[u8]) -> &[u8] {
+    let end = data.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    &data[..end]
+}
+
+/// A minimal element of a parsed XML document, as produced by
+/// [`XmlExtension`]. This is not a general-purpose XML library: it only
+/// supports the well-formed, attribute-bearing, text-or-element-content
+/// documents used by the AFNI and XCEDE extensions.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct XmlNode {
+    /// The element's tag name.
+    pub tag: String,
+    /// The element's attributes, in document order.
+    pub attrs: Vec<(String, String)>,
+    /// The element's child nodes, in document order.
+    pub children: Vec<XmlNode>,
+    /// Text content directly under this element (concatenated, trimmed).
+    pub text: String,
+}
+
+impl XmlNode {
+    /// Find the first direct child with the given tag name.
+    pub fn child(&self, tag: &str) -> Option<&XmlNode> {
+        self.children.iter().find(|c| c.tag == tag)
+    }
+
+    /// Get the value of an attribute by name.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// An AFNI or XCEDE extension (`NiftiEcodeAFNI`, `NiftiEcodeXCEDE`),
+/// decoded as an XML document tree.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct XmlExtension {
+    /// The source `ecode` this extension was decoded from (AFNI or XCEDE).
+    pub ecode: i32,
+    /// The root element of the parsed document.
+    pub root: XmlNode,
+}
+
+impl ExtensionCodec for XmlExtension {
+    fn decode(extension: &Extension) -> Result<Self> {
+        let text = std::str::from_utf8(trim_trailing_nul(extension.data()))
+            .map_err(|_| NiftiError::InvalidExtensionPayload(extension.code()))?;
+        let root = parse_xml(text)
+            .ok_or_else(|| NiftiError::InvalidExtensionPayload(extension.code()))?;
+        Ok(XmlExtension {
+            ecode: extension.code(),
+            root,
+        })
+    }
+
+    fn encode(&self) -> Extension {
+        Extension::from_str(self.ecode, &render_xml(&self.root))
+    }
+}
+
+/// A DICOM extension (`NiftiEcodeDicom`), interpreted as a sequence of
+/// explicit-VR little-endian data elements.
+///
+/// The payload is not copied out eagerly: [`DicomExtension::find_tag`]
+/// walks the length-prefixed elements in place, skipping over the ones
+/// that don't match.
+///
+/// Unlike [`CommentExtension`] and [`XmlExtension`], this is a borrowing
+/// view rather than an owned value, so it does *not* implement
+/// [`ExtensionCodec`] (whose `decode` has no lifetime to tie `Self` to the
+/// source `Extension`) and is not reachable through
+/// `Extension::decode_as`. Construct it directly from an extension's data
+/// instead: `DicomExtension::new(extension.data())`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DicomExtension<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DicomExtension<'a> {
+    /// Wrap a DICOM byte stream (typically an extension's raw `edata`).
+    pub fn new(data: &'a [u8]) -> Self {
+        DicomExtension { data }
+    }
+
+    /// Find the value bytes of the data element with the given DICOM tag
+    /// (group, element), without copying any of the elements it skips
+    /// over.
+    pub fn find_tag(&self, group: u16, element: u16) -> Option<&'a [u8]> {
+        self.iter().find(|e| e.group == group && e.element == element).map(|e| e.value)
+    }
+
+    /// Iterate over the data elements in this DICOM byte stream, in order.
+    pub fn iter(&self) -> DicomElementIter<'a> {
+        DicomElementIter { data: self.data }
+    }
+
+    /// Encode this view back into an owned [`Extension`], padded to a
+    /// multiple of 16 bytes like every other extension.
+    pub fn encode(&self) -> Extension {
+        let esize = 8 + self.data.len() as i32;
+        let padded_esize = (esize + 15) & !15;
+        let mut data = self.data.to_vec();
+        data.resize(padded_esize as usize - 8, 0);
+        Extension::new(padded_esize, NiftiEcode::NiftiEcodeDicom as i32, data)
+    }
+}
+
+/// A single DICOM data element, as yielded by [`DicomExtension::iter`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DicomElement<'a> {
+    /// The tag's group number.
+    pub group: u16,
+    /// The tag's element number.
+    pub element: u16,
+    /// The element's raw value bytes.
+    pub value: &'a [u8],
+}
+
+/// Iterator over the data elements of a [`DicomExtension`].
+pub struct DicomElementIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for DicomElementIter<'a> {
+    type Item = DicomElement<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Tag (4 bytes) + VR (2 bytes) + length, whose width depends on VR.
+        if self.data.len() < 8 {
+            return None;
+        }
+        let group = u16::from_le_bytes([self.data[0], self.data[1]]);
+        let element = u16::from_le_bytes([self.data[2], self.data[3]]);
+        let vr = &self.data[4..6];
+
+        let (header_len, value_len) = if matches!(vr, b"OB" | b"OW" | b"OF" | b"SQ" | b"UT" | b"UN")
+        {
+            if self.data.len() < 12 {
+                return None;
+            }
+            let len = u32::from_le_bytes([
+                self.data[8],
+                self.data[9],
+                self.data[10],
+                self.data[11],
+            ]);
+            (12, len as usize)
+        } else {
+            let len = u16::from_le_bytes([self.data[6], self.data[7]]);
+            (8, len as usize)
+        };
+
+        if self.data.len() < header_len + value_len {
+            return None;
+        }
+
+        let value = &self.data[header_len..header_len + value_len];
+        self.data = &self.data[header_len + value_len..];
+        Some(DicomElement {
+            group,
+            element,
+            value,
+        })
+    }
+}
+
+/// A tiny, dependency-free XML parser covering the subset used by AFNI
+/// and XCEDE extensions: nested elements, attributes and text content.
+/// Returns `None` on anything it cannot make sense of.
+fn parse_xml(text: &str) -> Option<XmlNode> {
+    let mut chars = text.char_indices().peekable();
+    skip_prolog(text, &mut chars);
+    parse_element(text, &mut chars)
+}
+
+fn skip_prolog(text: &str, chars: &mut std::iter::Peekable<std::str::CharIndices>) {
+    loop {
+        skip_whitespace(chars);
+        let rest = match chars.peek() {
+            Some(&(i, _)) => &text[i..],
+            None => return,
+        };
+        if rest.starts_with("<?") {
+            while let Some((_, c)) = chars.next() {
+                if c == '>' {
+                    break;
+                }
+            }
+        } else if rest.starts_with("<!--") {
+            consume_comment(text, chars);
+        } else {
+            break;
+        }
+    }
+}
+
+fn consume_comment(text: &str, chars: &mut std::iter::Peekable<std::str::CharIndices>) {
+    for _ in 0..4 {
+        chars.next();
+    }
+    while let Some((i, _)) = chars.next() {
+        if text[i..].starts_with("-->") {
+            chars.next();
+            chars.next();
+            break;
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::CharIndices>) {
+    while let Some(&(_, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else {
+            break;
+        }
+    }
+}
+
+fn parse_element(
+    text: &str,
+    chars: &mut std::iter::Peekable<std::str::CharIndices>,
+) -> Option<XmlNode> {
+    skip_whitespace(chars);
+    if chars.next()?.1 != '<' {
+        return None;
+    }
+    let tag = take_while(text, chars, |c| !c.is_whitespace() && c != '>' && c != '/');
+    let mut attrs = Vec::new();
+    loop {
+        skip_whitespace(chars);
+        match chars.peek()?.1 {
+            '/' => {
+                chars.next();
+                if chars.next()?.1 != '>' {
+                    return None;
+                }
+                return Some(XmlNode {
+                    tag,
+                    attrs,
+                    children: Vec::new(),
+                    text: String::new(),
+                });
+            }
+            '>' => {
+                chars.next();
+                break;
+            }
+            _ => {
+                let name = take_while(text, chars, |c| c != '=' && !c.is_whitespace());
+                skip_whitespace(chars);
+                if chars.next()?.1 != '=' {
+                    return None;
+                }
+                skip_whitespace(chars);
+                let quote = chars.next()?.1;
+                let value = unescape_xml(&take_while(text, chars, |c| c != quote));
+                chars.next();
+                attrs.push((name, value));
+            }
+        }
+    }
+
+    let mut children = Vec::new();
+    let mut node_text = String::new();
+    loop {
+        skip_whitespace(chars);
+        let rest = &text[chars.peek()?.0..];
+        if rest.starts_with("</") {
+            for _ in 0..2 {
+                chars.next();
+            }
+            let _closing = take_while(text, chars, |c| c != '>');
+            chars.next();
+            break;
+        } else if rest.starts_with("<!--") {
+            consume_comment(text, chars);
+        } else if rest.starts_with('<') {
+            children.push(parse_element(text, chars)?);
+        } else {
+            node_text.push_str(&take_while(text, chars, |c| c != '<'));
+        }
+    }
+
+    Some(XmlNode {
+        tag,
+        attrs,
+        children,
+        text: unescape_xml(node_text.trim()),
+    })
+}
+
+/// Unescape the XML built-in entities (`&amp;`, `&lt;`, `&gt;`,
+/// `&quot;`, `&apos;`) in text or attribute content. Unknown entities are
+/// passed through unchanged.
+fn unescape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '&' {
+            out.push(c);
+            continue;
+        }
+        let mut entity = String::new();
+        let mut closed = false;
+        while let Some(&c2) = chars.peek() {
+            if c2 == ';' {
+                chars.next();
+                closed = true;
+                break;
+            }
+            if !c2.is_alphanumeric() || entity.len() > 8 {
+                break;
+            }
+            entity.push(c2);
+            chars.next();
+        }
+        if closed {
+            match entity.as_str() {
+                "amp" => out.push('&'),
+                "lt" => out.push('<'),
+                "gt" => out.push('>'),
+                "quot" => out.push('"'),
+                "apos" => out.push('\''),
+                _ => {
+                    out.push('&');
+                    out.push_str(&entity);
+                    out.push(';');
+                }
+            }
+        } else {
+            out.push('&');
+            out.push_str(&entity);
+        }
+    }
+    out
+}
+
+fn take_while(
+    text: &str,
+    chars: &mut std::iter::Peekable<std::str::CharIndices>,
+    pred: impl Fn(char) -> bool,
+) -> String {
+    let start = chars.peek().map(|&(i, _)| i).unwrap_or(text.len());
+    let mut end = start;
+    while let Some(&(i, c)) = chars.peek() {
+        if !pred(c) {
+            break;
+        }
+        end = i + c.len_utf8();
+        chars.next();
+    }
+    text[start..end].to_string()
+}
+
+fn render_xml(node: &XmlNode) -> String {
+    let mut out = format!("<{}", node.tag);
+    for (k, v) in &node.attrs {
+        out.push_str(&format!(" {}=\"{}\"", k, escape_xml(v)));
+    }
+    if node.children.is_empty() && node.text.is_empty() {
+        out.push_str("/>");
+        return out;
+    }
+    out.push('>');
+    out.push_str(&escape_xml(&node.text));
+    for child in &node.children {
+        out.push_str(&render_xml(child));
+    }
+    out.push_str(&format!("</{}>", node.tag));
+    out
+}
+
+/// Escape the characters that are significant to XML markup (`&`, `<`,
+/// `>`, `"`) so that arbitrary text and attribute values round-trip
+/// through [`parse_xml`]/`unescape_xml`.
+fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -4,10 +4,26 @@
 //! end of the NIFTI-1 header, with the first byte set to something
 //! other than 0.
 
+pub mod codec;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+
+pub use self::codec::{
+    CommentExtension, DicomElement, DicomElementIter, DicomExtension, ExtensionCodec,
+    XmlExtension, XmlNode,
+};
+
 use crate::error::{NiftiError, Result};
 use byteordered::{ByteOrdered, Endian};
 use num_derive::FromPrimitive;
-use std::io::{ErrorKind as IoErrorKind, Read};
+use std::io::{ErrorKind as IoErrorKind, Read, Write};
+
+/// Upper bound on how many bytes are reserved up-front for an extension's
+/// data while reading it from a stream. The on-disk `esize` field is not
+/// trusted beyond this point: larger extensions are grown incrementally as
+/// bytes actually arrive, rather than reserved all at once, so that a
+/// corrupt or adversarial `esize` cannot force an oversized allocation.
+const PREALLOC_MAX_SIZE: usize = 32 * 1024 * 1024; // 32 MiB
 
 /// Data type for representing a NIfTI-1.1 extension code
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, FromPrimitive)]
@@ -134,6 +150,35 @@ impl Extension {
     pub fn into_data(self) -> Vec<u8> {
         self.edata
     }
+
+    /// Interpret this extension's raw data through the given codec.
+    ///
+    /// This is a convenience method equivalent to calling `C::decode(self)`.
+    /// Note that [`DicomExtension`] is not reachable this way, since it
+    /// borrows from the extension's data rather than owning it; construct
+    /// it directly with `DicomExtension::new(extension.data())`.
+    pub fn decode_as<C: ExtensionCodec>(&self) -> Result<C> {
+        C::decode(self)
+    }
+
+    /// Write this extension to a writer: the `esize`/`ecode` pair
+    /// (honoring the given endianness), followed by `edata`.
+    ///
+    /// `esize` is written verbatim, so the extension must already be
+    /// 16-byte aligned (as `Extension::new`/`Extension::from_str`
+    /// produce); call [`ExtensionSequence::validate`] first if in doubt.
+    pub fn write_to<W, E>(&self, writer: &mut ByteOrdered<W, E>) -> Result<()>
+    where
+        W: Write,
+        E: Endian,
+    {
+        debug_assert_eq!(self.esize as usize, 8 + self.edata.len());
+
+        writer.write_i32(self.esize)?;
+        writer.write_i32(self.ecode)?;
+        writer.write_all(&self.edata)?;
+        Ok(())
+    }
 }
 
 /// Data type for aggregating the extender code and
@@ -188,21 +233,39 @@ impl ExtensionSequence {
                 let esize = source.read_i32()?;
                 let ecode = source.read_i32()?;
 
-                let data_size = (esize as usize).saturating_sub(8);
+                if esize < 8 || esize % 16 != 0 {
+                    return Err(NiftiError::InvalidExtensionSize(esize));
+                }
+                let esize = esize as usize;
+                if offset + esize > len {
+                    return Err(NiftiError::IncompatibleLength(offset + esize, len));
+                }
+
+                let data_size = esize - 8;
                 let mut edata = Vec::new();
                 edata
-                    .try_reserve_exact(data_size)
+                    .try_reserve_exact(data_size.min(PREALLOC_MAX_SIZE))
                     .map_err(|e| NiftiError::ReserveExtended(data_size, e))?;
-                let nb_bytes_written = (&mut source)
-                    .take(data_size as u64)
-                    .read_to_end(&mut edata)?;
 
-                if nb_bytes_written != data_size {
-                    return Err(NiftiError::IncompatibleLength(nb_bytes_written, data_size));
+                let mut reader = (&mut source).take(data_size as u64);
+                let mut buf = [0u8; 8192];
+                loop {
+                    let n = reader.read(&mut buf)?;
+                    if n == 0 {
+                        break;
+                    }
+                    edata
+                        .try_reserve(n)
+                        .map_err(|e| NiftiError::ReserveExtended(data_size, e))?;
+                    edata.extend_from_slice(&buf[..n]);
                 }
 
-                extensions.push(Extension::new(i32::max(esize, 8), ecode, edata));
-                offset += esize as usize;
+                if edata.len() != data_size {
+                    return Err(NiftiError::IncompatibleLength(edata.len(), data_size));
+                }
+
+                extensions.push(Extension::new(esize as i32, ecode, edata));
+                offset += esize;
             }
         }
 
@@ -238,4 +301,50 @@ impl ExtensionSequence {
     pub fn extender(&self) -> Extender {
         self.extender
     }
+
+    /// Append an extension to the end of the sequence.
+    pub fn push(&mut self, extension: Extension) {
+        self.extensions.push(extension);
+    }
+
+    /// Check that every extension in this sequence is well-formed: its
+    /// `esize` is at least 8, a multiple of 16, and equal to `8 +
+    /// edata.len()`. A sequence built entirely through `push`,
+    /// `Extension::new` and `Extension::from_str` already satisfies this;
+    /// this is most useful after constructing extensions by hand.
+    pub fn validate(&self) -> Result<()> {
+        for extension in &self.extensions {
+            if extension.esize < 8 || extension.esize % 16 != 0 {
+                return Err(NiftiError::InvalidExtensionSize(extension.esize));
+            }
+            if extension.esize as usize != 8 + extension.edata.len() {
+                return Err(NiftiError::IncompatibleLength(
+                    extension.esize as usize,
+                    8 + extension.edata.len(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Write this sequence to a writer: the 4-byte extender, followed by
+    /// each extension in order. Rejects the sequence with a `NiftiError`
+    /// if any extension fails [`ExtensionSequence::validate`], since a
+    /// misaligned `esize` would otherwise make the bytes written disagree
+    /// with [`ExtensionSequence::bytes_on_disk`]. Otherwise, the result is
+    /// spec-compliant, so `vox_offset` can be recomputed from
+    /// `bytes_on_disk`.
+    pub fn write_to<W, E>(&self, mut writer: ByteOrdered<W, E>) -> Result<()>
+    where
+        W: Write,
+        E: Endian,
+    {
+        self.validate()?;
+
+        writer.write_all(self.extender.as_bytes())?;
+        for extension in &self.extensions {
+            extension.write_to(&mut writer)?;
+        }
+        Ok(())
+    }
 }
@@ -33,7 +33,15 @@ where
         T: Add<Output = T>,
         T: PodTransmutable
     {
-        // TODO optimize this implementation (we don't need the whole volume)
+        // Deferred: a lazy, allocation-free slab read would need to reach
+        // into a volume's raw byte layout (e.g. an in-memory volume
+        // backed by a `Vec<u8>` plus its dimensions) to pull out just the
+        // requested slice. Neither such a type nor a lower-level
+        // `NiftiVolume` accessor for it is available in this module, so
+        // there is nothing concrete to build the optimization on here;
+        // decoding the whole volume and subviewing it remains correct,
+        // just not allocation-optimal. Revisit once a raw-byte-addressable
+        // volume type's internals are in scope for this module.
         let volume = self.volume.to_ndarray()?;
         Ok(volume.into_subview(Axis(self.axis as Ix), self.index as usize))
     }